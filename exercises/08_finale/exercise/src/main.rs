@@ -1,7 +1,31 @@
-use std::{cmp, result};
+use std::cmp::Reverse;
+use std::collections::HashMap;
 
 use require_lifetimes::require_lifetimes;
 
+/// How many times the token wrapped by a [`MatcherToken::Repeat`] may occur.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum RepeatKind {
+    /// `*` — the inner token may match any number of times, including zero.
+    ZeroOrMore,
+    /// `+` — the inner token must match at least once.
+    OneOrMore,
+    /// `?` — the inner token may match zero or one time.
+    Optional,
+}
+
+impl RepeatKind {
+    /// Reads the repetition operator from the character following a token, if any.
+    fn from_char(c: char) -> Option<RepeatKind> {
+        match c {
+            '*' => Some(RepeatKind::ZeroOrMore),
+            '+' => Some(RepeatKind::OneOrMore),
+            '?' => Some(RepeatKind::Optional),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 enum MatcherToken<'a> {
     /// This is just text without anything special.
@@ -13,8 +37,107 @@ enum MatcherToken<'a> {
     /// This is when you're happy to accept any single character.
     /// It looks like `.`
     WildCard,
+    /// This is a token that may repeat, written as a trailing `*`, `+` or `?`
+    /// after any other token (e.g. `(a|b)*`).
+    Repeat {
+        inner: Box<MatcherToken<'a>>,
+        kind: RepeatKind,
+    },
+}
+
+impl<'a> MatcherToken<'a> {
+    /// Tries to match this token against the start of `input`, returning the
+    /// matched slice if it does. [`MatcherToken::Repeat`] is handled by the NFA
+    /// in [`Matcher::run`] rather than here, so it never matches directly.
+    fn match_prefix<'s>(&self, input: &'s str) -> Option<&'s str> {
+        match self {
+            MatcherToken::RawText(raw) => input.starts_with(*raw).then(|| &input[..raw.len()]),
+            MatcherToken::OneOfText(variants) => {
+                for variant in variants {
+                    if input.starts_with(*variant) {
+                        return Some(&input[..variant.len()]);
+                    }
+                }
+                None
+            }
+            MatcherToken::WildCard => input.chars().next().map(|c| &input[..c.len_utf8()]),
+            MatcherToken::Repeat { .. } => None,
+        }
+    }
+
+    /// Like [`MatcherToken::match_prefix`], but [`MatcherToken::RawText`] tokens
+    /// match within `max_distance` edits; every other token keeps its exact
+    /// semantics. Returns the matched slice together with the edit distance it
+    /// was achieved at (always `0` for the exact tokens).
+    fn match_prefix_fuzzy<'s>(&self, input: &'s str, max_distance: u8) -> Option<(&'s str, u8)> {
+        match self {
+            MatcherToken::RawText(raw) => fuzzy_prefix(raw, input, max_distance),
+            MatcherToken::Repeat { .. } => None,
+            _ => self.match_prefix(input).map(|matched| (matched, 0)),
+        }
+    }
+}
+
+/// Offers a fuzzy candidate to `best`, keeping the one with the smallest edit
+/// distance and, on a tie, the longest matched input.
+fn offer_candidate(best: &mut Option<(usize, u8)>, byte_len: usize, distance: usize, max: usize) {
+    if distance > max {
+        return;
+    }
+    let distance = distance as u8;
+    let better = match *best {
+        None => true,
+        Some((len, d)) => distance < d || (distance == d && byte_len > len),
+    };
+    if better {
+        *best = Some((byte_len, distance));
+    }
+}
+
+/// Best fuzzy match of `pattern` against a prefix of `input`, within
+/// `max_distance` edits (insertions, deletions, substitutions).
+///
+/// This is a banded Levenshtein computation: a single DP row over `pattern` is
+/// advanced one input character at a time, and the scan stops as soon as every
+/// cell in the row has drifted outside the edit budget, so it stays within a
+/// band around the diagonal. The returned slice is the shortest-distance input
+/// prefix whose distance to the whole pattern is `<= max_distance`.
+fn fuzzy_prefix<'s>(pattern: &str, input: &'s str, max_distance: u8) -> Option<(&'s str, u8)> {
+    let max = max_distance as usize;
+    let pat: Vec<char> = pattern.chars().collect();
+    let m = pat.len();
+
+    // `prev[j]` is the edit distance between the input consumed so far and the
+    // first `j` characters of the pattern.
+    let mut prev: Vec<usize> = (0..=m).collect();
+    let mut best: Option<(usize, u8)> = None;
+    offer_candidate(&mut best, 0, prev[m], max);
+
+    let mut offset = 0;
+    for (i, c) in input.chars().enumerate() {
+        offset += c.len_utf8();
+        let mut cur = vec![0usize; m + 1];
+        cur[0] = i + 1;
+        let mut row_min = cur[0];
+        for j in 1..=m {
+            let sub_cost = usize::from(pat[j - 1] != c);
+            cur[j] = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + sub_cost);
+            row_min = row_min.min(cur[j]);
+        }
+        offer_candidate(&mut best, offset, cur[m], max);
+        prev = cur;
+        if row_min > max {
+            break;
+        }
+    }
+
+    best.map(|(byte_len, distance)| (&input[..byte_len], distance))
 }
 
+/// A matched path: the `(token, slice, edit-distance)` triples produced by
+/// [`Matcher::run`] for one route through the pattern.
+type Path<'a, 'b, 'c> = Vec<(&'b MatcherToken<'a>, &'c str, u8)>;
+
 #[derive(Debug, PartialEq, Eq)]
 struct Matcher<'a> {
     /// This is the actual text of the matcher
@@ -25,6 +148,52 @@ struct Matcher<'a> {
     most_tokens_matched: usize,
 }
 
+/// A single occurrence of a [`Matcher`]'s pattern inside some input, as yielded
+/// by [`Matcher::matches`]. `start`/`end` are absolute byte offsets into that
+/// input, and `tokens` is the `(token, slice)` breakdown for this occurrence.
+#[derive(Debug, PartialEq, Eq)]
+struct Match<'a, 'b, 'c> {
+    start: usize,
+    end: usize,
+    tokens: Vec<(&'b MatcherToken<'a>, &'c str)>,
+}
+
+/// Iterator returned by [`Matcher::matches`], scanning an input for every
+/// non-overlapping occurrence of the pattern.
+struct Matches<'a, 'b, 'c> {
+    tokens: &'b [MatcherToken<'a>],
+    input: &'c str,
+    pos: usize,
+}
+
+impl<'a, 'b, 'c> Iterator for Matches<'a, 'b, 'c> {
+    type Item = Match<'a, 'b, 'c>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos <= self.input.len() {
+            let (end, full, path) = Matcher::run(self.tokens, &self.input[self.pos..], None);
+            // A complete, non-empty match anchored here is an occurrence; emit it
+            // and resume scanning just past its end.
+            if full && end > 0 {
+                let start = self.pos;
+                self.pos += end;
+                let tokens = path.into_iter().map(|(token, slice, _)| (token, slice)).collect();
+                return Some(Match {
+                    start,
+                    end: start + end,
+                    tokens,
+                });
+            }
+            // Otherwise advance to the next character boundary and try again.
+            match self.input[self.pos..].chars().next() {
+                Some(c) => self.pos += c.len_utf8(),
+                None => return None,
+            }
+        }
+        None
+    }
+}
+
 impl<'a> Matcher<'a> {
     /// This should take a string reference, and return
     /// an `Matcher` which has parsed that reference.
@@ -33,36 +202,43 @@ impl<'a> Matcher<'a> {
         let mut tokens = vec![];
         let mut leftovers = text;
 
-        while leftovers.len() > 0 {
-            if leftovers.starts_with(".") {
-                tokens.push(MatcherToken::WildCard);
+        while !leftovers.is_empty() {
+            let token = if leftovers.starts_with('.') {
                 leftovers = &leftovers[1..];
-            } else if leftovers.starts_with("(") {
-                let Some(close_index) = leftovers.find(")") else { return None };
-
-                let micro_tokens =
-                    MatcherToken::OneOfText(leftovers[1..close_index].split("|").collect());
-                tokens.push(micro_tokens);
-
-                if (close_index + 1) < leftovers.len() {
-                    leftovers = &leftovers[close_index + 1..];
-                } else {
-                    break;
-                }
+                MatcherToken::WildCard
+            } else if leftovers.starts_with('(') {
+                let close_index = leftovers.find(')')?;
+                let variants = leftovers[1..close_index].split('|').collect();
+                leftovers = &leftovers[close_index + 1..];
+                MatcherToken::OneOfText(variants)
             } else {
-                let next_separator = match (leftovers.find("."), leftovers.find("(")) {
-                    (Some(a), Some(b)) => Some(cmp::min(a, b)),
-                    (None, Some(a)) | (Some(a), None) => Some(a),
-                    (None, None) => None,
-                };
+                let mut end = leftovers
+                    .find(['.', '(', '*', '+', '?'])
+                    .unwrap_or(leftovers.len());
+                // A repetition operator binds to the character it immediately
+                // follows, so peel the last character of a longer run off into
+                // its own token for the operator to wrap.
+                let last_len = leftovers[..end].chars().next_back().map_or(0, char::len_utf8);
+                if end > last_len && leftovers[end..].starts_with(['*', '+', '?']) {
+                    end -= last_len;
+                }
+                let raw = &leftovers[..end];
+                leftovers = &leftovers[end..];
+                MatcherToken::RawText(raw)
+            };
 
-                if let Some(index) = next_separator {
-                    tokens.push(MatcherToken::RawText(&leftovers[..index]));
-                    leftovers = &leftovers[index..];
-                } else {
-                    tokens.push(MatcherToken::RawText(&leftovers))
+            // A trailing `*`, `+` or `?` wraps the token we just parsed.
+            let token = match leftovers.chars().next().and_then(RepeatKind::from_char) {
+                Some(kind) => {
+                    leftovers = &leftovers[1..];
+                    MatcherToken::Repeat {
+                        inner: Box::new(token),
+                        kind,
+                    }
                 }
-            }
+                None => token,
+            };
+            tokens.push(token);
         }
 
         Some(Matcher {
@@ -76,38 +252,405 @@ impl<'a> Matcher<'a> {
     /// of the given string. For examples, see the test cases below.
     #[require_lifetimes]
     fn match_string<'b, 'c>(&'b mut self, string: &'c str) -> Vec<(&'b MatcherToken<'a>, &'c str)> {
-        let mut answer = vec![];
-        let mut substring = string;
+        let (_, _, answer) = Self::run(&self.tokens, string, None);
+        self.most_tokens_matched = answer.len();
+        answer.into_iter().map(|(token, slice, _)| (token, slice)).collect()
+    }
+
+    /// Like [`Matcher::match_string`], but [`MatcherToken::RawText`] tokens match
+    /// input prefixes within `max_distance` edits instead of requiring an exact
+    /// prefix; `WildCard` and `OneOfText` keep their exact semantics. Each
+    /// returned tuple carries the edit distance its token was matched at, so
+    /// callers can rank results by total distance.
+    #[require_lifetimes]
+    fn match_string_fuzzy<'b, 'c>(
+        &'b mut self,
+        string: &'c str,
+        max_distance: u8,
+    ) -> Path<'a, 'b, 'c> {
+        let (_, _, answer) = Self::run(&self.tokens, string, Some(max_distance));
+        self.most_tokens_matched = answer.len();
+        answer
+    }
+
+    /// Scans `input` from start to finish and yields every non-overlapping
+    /// occurrence of the pattern, each with its absolute byte span and
+    /// `(token, slice)` breakdown. Unlike [`Matcher::match_string`], this is
+    /// immutable and does not touch `most_tokens_matched`.
+    fn matches<'b, 'c>(&'b self, input: &'c str) -> impl Iterator<Item = Match<'a, 'b, 'c>> {
+        Matches {
+            tokens: &self.tokens,
+            input,
+            pos: 0,
+        }
+    }
+
+    /// Runs the tokens against `string` as a thread-set NFA, returning the
+    /// byte offset into `string` at which the chosen path ends, whether that
+    /// path is a complete match of every token, and the path itself. The
+    /// longest complete path is chosen when one exists; otherwise the longest
+    /// partial path reachable from the start.
+    ///
+    /// A "position" is a `(token_index, offset)` pair. Plain tokens spawn a
+    /// single successor when they match; a [`MatcherToken::Repeat`] spawns a
+    /// skip successor (when zero matches are allowed) and, when its inner token
+    /// matches, a consume successor that either stays on the same token (to
+    /// repeat) or moves on. Positions are deduplicated by `(token_index,
+    /// offset)` so pathological inputs cannot cause exponential blow-up.
+    ///
+    /// When `fuzz` is `Some(max_distance)`, `RawText` tokens are matched within
+    /// that edit distance (see [`MatcherToken::match_prefix_fuzzy`]); the third
+    /// element of each tuple is the distance the token matched at.
+    fn run<'b, 'c>(
+        tokens: &'b [MatcherToken<'a>],
+        string: &'c str,
+        fuzz: Option<u8>,
+    ) -> (usize, bool, Path<'a, 'b, 'c>) {
+        struct Thread<'a, 'b, 'c> {
+            token_index: usize,
+            offset: usize,
+            path: Path<'a, 'b, 'c>,
+        }
+
+        let match_prefix = |token: &'b MatcherToken<'a>, input: &'c str| match fuzz {
+            Some(max) => token.match_prefix_fuzzy(input, max),
+            None => token.match_prefix(input).map(|matched| (matched, 0)),
+        };
+
+        // The longest path reached at all (used for partial matches), and the
+        // longest path that reached the accepting state (`token_index ==
+        // tokens.len()`). A complete match must come from an accepting thread,
+        // which is not necessarily the globally longest one — e.g. when the last
+        // token is a `Repeat`, the longest thread can sit on the repeat itself.
+        let mut best: Path<'a, 'b, 'c> = vec![];
+        let mut best_offset = 0;
+        let mut accept: Option<(usize, Path<'a, 'b, 'c>)> = None;
+        // The longest path length with which we have already reached a given
+        // position; a shorter (or equal) arrival can never do better from here.
+        let mut seen: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut stack = vec![Thread {
+            token_index: 0,
+            offset: 0,
+            path: vec![],
+        }];
+
+        while let Some(Thread {
+            token_index,
+            offset,
+            path,
+        }) = stack.pop()
+        {
+            if seen.get(&(token_index, offset)).is_some_and(|&len| len >= path.len()) {
+                continue;
+            }
+            seen.insert((token_index, offset), path.len());
+
+            if path.len() > best.len() || (path.len() == best.len() && offset > best_offset) {
+                best = path.clone();
+                best_offset = offset;
+            }
+            if token_index == tokens.len() {
+                let better = match &accept {
+                    None => true,
+                    Some((off, p)) => path.len() > p.len() || (path.len() == p.len() && offset > *off),
+                };
+                if better {
+                    accept = Some((offset, path.clone()));
+                }
+            }
+
+            let Some(token) = tokens.get(token_index) else {
+                continue;
+            };
 
-        'outer_loop: for token in &self.tokens {
             match token {
-                MatcherToken::RawText(raw_text) => {
-                    if substring.starts_with(raw_text) {
-                        answer.push((token, &substring[..raw_text.len()]));
-                        substring = &substring[raw_text.len()..];
-                    } else {
-                        break;
+                MatcherToken::Repeat { inner, kind } => {
+                    if matches!(kind, RepeatKind::ZeroOrMore | RepeatKind::Optional) {
+                        stack.push(Thread {
+                            token_index: token_index + 1,
+                            offset,
+                            path: path.clone(),
+                        });
                     }
-                }
-                MatcherToken::OneOfText(variants) => {
-                    for variant in variants {
-                        if substring.starts_with(variant) {
-                            answer.push((token, &substring[..variant.len()]));
-                            substring = &substring[variant.len()..];
-                            continue 'outer_loop;
+                    let inner = inner.as_ref();
+                    if let Some((matched, distance)) = match_prefix(inner, &string[offset..])
+                        .filter(|(matched, _)| !matched.is_empty())
+                    {
+                        let mut consumed = path.clone();
+                        consumed.push((inner, matched, distance));
+                        let next_offset = offset + matched.len();
+                        match kind {
+                            RepeatKind::Optional => stack.push(Thread {
+                                token_index: token_index + 1,
+                                offset: next_offset,
+                                path: consumed,
+                            }),
+                            RepeatKind::ZeroOrMore => stack.push(Thread {
+                                token_index,
+                                offset: next_offset,
+                                path: consumed,
+                            }),
+                            RepeatKind::OneOrMore => {
+                                stack.push(Thread {
+                                    token_index: token_index + 1,
+                                    offset: next_offset,
+                                    path: consumed.clone(),
+                                });
+                                stack.push(Thread {
+                                    token_index,
+                                    offset: next_offset,
+                                    path: consumed,
+                                });
+                            }
                         }
                     }
-                    break;
                 }
-                MatcherToken::WildCard => {
-                    answer.push((token, &substring[..1]));
-                    substring = &substring[1..];
+                plain => {
+                    if let Some((matched, distance)) = match_prefix(plain, &string[offset..]) {
+                        let mut next = path.clone();
+                        next.push((plain, matched, distance));
+                        stack.push(Thread {
+                            token_index: token_index + 1,
+                            offset: offset + matched.len(),
+                            path: next,
+                        });
+                    }
                 }
             }
         }
-        self.most_tokens_matched = answer.len();
 
-        answer
+        // Prefer a complete match when one exists; otherwise report the longest
+        // partial path so `match_string` keeps its best-effort behaviour.
+        match accept {
+            Some((offset, path)) => (offset, true, path),
+            None => (best_offset, false, best),
+        }
+    }
+}
+
+/// Returns the byte offset `n` characters after `from` in `s`, clamped to the
+/// end of the string.
+fn advance_chars(s: &str, from: usize, n: usize) -> usize {
+    let mut end = from;
+    for c in s[from..].chars().take(n) {
+        end += c.len_utf8();
+    }
+    end
+}
+
+/// Number of distinct pattern tokens (by identity) matched across `matches`.
+fn distinct_tokens(matches: &[&Match]) -> usize {
+    let mut seen: Vec<usize> = vec![];
+    for m in matches {
+        for (token, _) in &m.tokens {
+            let addr = *token as *const MatcherToken<'_> as usize;
+            if !seen.contains(&addr) {
+                seen.push(addr);
+            }
+        }
+    }
+    seen.len()
+}
+
+/// Total gap, in bytes, between consecutive matched spans in `matches`
+/// (which are assumed to be ordered by `start`).
+fn total_gap(matches: &[&Match]) -> usize {
+    matches
+        .windows(2)
+        .map(|pair| pair[1].start.saturating_sub(pair[0].end))
+        .sum()
+}
+
+/// Position of `token` in `matcher`'s pattern, matching both top-level tokens
+/// and the inner token of a [`MatcherToken::Repeat`] (which is what the NFA
+/// records for a repeated match).
+fn pattern_index(matcher: &Matcher, token: &MatcherToken) -> Option<usize> {
+    matcher.tokens.iter().position(|candidate| {
+        std::ptr::eq(candidate, token)
+            || matches!(candidate, MatcherToken::Repeat { inner, .. } if std::ptr::eq(inner.as_ref(), token))
+    })
+}
+
+/// How many matched tokens inside `matches` follow the pattern's token order:
+/// the length of the longest strictly increasing subsequence of pattern positions,
+/// i.e. how far the window's matches progress through the pattern in order.
+fn in_order_count(matches: &[&Match], matcher: &Matcher) -> usize {
+    let indices: Vec<usize> = matches
+        .iter()
+        .flat_map(|m| m.tokens.iter())
+        .filter_map(|&(token, _)| pattern_index(matcher, token))
+        .collect();
+
+    // Longest strictly increasing subsequence; the input is tiny, so the
+    // quadratic formulation is perfectly adequate.
+    let mut lengths = vec![0usize; indices.len()];
+    let mut best = 0;
+    for i in 0..indices.len() {
+        lengths[i] = 1;
+        for j in 0..i {
+            if indices[j] < indices[i] {
+                lengths[i] = lengths[i].max(lengths[j] + 1);
+            }
+        }
+        best = best.max(lengths[i]);
+    }
+    best
+}
+
+/// Configurable renderer that crops a long input to the most relevant window
+/// around a [`Matcher`]'s matches and wraps each matched slice in markers. This
+/// is the snippet-generation layer on top of the raw matcher.
+struct MatcherBuilder {
+    highlight_prefix: String,
+    highlight_suffix: String,
+    crop_marker: String,
+}
+
+impl Default for MatcherBuilder {
+    fn default() -> MatcherBuilder {
+        MatcherBuilder {
+            highlight_prefix: "<em>".to_string(),
+            highlight_suffix: "</em>".to_string(),
+            crop_marker: "…".to_string(),
+        }
+    }
+}
+
+impl MatcherBuilder {
+    fn new() -> MatcherBuilder {
+        MatcherBuilder::default()
+    }
+
+    fn highlight_prefix(mut self, marker: &str) -> MatcherBuilder {
+        self.highlight_prefix = marker.to_string();
+        self
+    }
+
+    fn highlight_suffix(mut self, marker: &str) -> MatcherBuilder {
+        self.highlight_suffix = marker.to_string();
+        self
+    }
+
+    fn crop_marker(mut self, marker: &str) -> MatcherBuilder {
+        self.crop_marker = marker.to_string();
+        self
+    }
+
+    /// Crops `input` to a window of `window` characters around the most relevant
+    /// run of matches and highlights each matched slice inside it.
+    fn format(&self, matcher: &Matcher, input: &str, window: usize) -> String {
+        let matches: Vec<Match> = matcher.matches(input).collect();
+        let (start, end) = self.best_window(matcher, &matches, input, window);
+
+        let mut out = String::new();
+        if start > 0 {
+            out.push_str(&self.crop_marker);
+        }
+        let mut cursor = start;
+        for m in matches.iter().filter(|m| m.start >= start && m.end <= end) {
+            out.push_str(&input[cursor..m.start]);
+            out.push_str(&self.highlight_prefix);
+            out.push_str(&input[m.start..m.end]);
+            out.push_str(&self.highlight_suffix);
+            cursor = m.end;
+        }
+        out.push_str(&input[cursor..end]);
+        if end < input.len() {
+            out.push_str(&self.crop_marker);
+        }
+        out
+    }
+
+    /// Picks the `[start, end)` byte window of `window` characters that best
+    /// covers the matches, ranking candidate windows (anchored at each match's
+    /// start) by most distinct tokens, then smallest total gap, then most
+    /// matched tokens in pattern order.
+    fn best_window(
+        &self,
+        matcher: &Matcher,
+        matches: &[Match],
+        input: &str,
+        window: usize,
+    ) -> (usize, usize) {
+        if matches.is_empty() {
+            return (0, advance_chars(input, 0, window));
+        }
+
+        let mut best: Option<((usize, isize, usize), usize, usize)> = None;
+        for anchor in matches {
+            let start = anchor.start;
+            let end = advance_chars(input, start, window);
+            let inside: Vec<&Match> = matches
+                .iter()
+                .filter(|m| m.start >= start && m.end <= end)
+                .collect();
+            let score = (
+                distinct_tokens(&inside),
+                -(total_gap(&inside) as isize),
+                in_order_count(&inside, matcher),
+            );
+            let better = match best {
+                None => true,
+                Some((best_score, ..)) => score > best_score,
+            };
+            if better {
+                best = Some((score, start, end));
+            }
+        }
+
+        let (_, start, end) = best.unwrap();
+        (start, end)
+    }
+}
+
+/// A single pattern from a [`MatcherSet`] that matched an input, as returned by
+/// [`MatcherSet::matches`]: the index of the pattern in the set and its
+/// `(token, slice)` breakdown.
+#[derive(Debug, PartialEq, Eq)]
+struct SetMatch<'a, 'b, 'c> {
+    pattern: usize,
+    tokens: Vec<(&'b MatcherToken<'a>, &'c str)>,
+}
+
+/// A collection of patterns compiled together, so an input can be classified
+/// against all of them with one call — analogous to `regex::RegexSet`. Each
+/// pattern is still run over the input separately; the patterns share no work.
+struct MatcherSet<'a> {
+    matchers: Vec<Matcher<'a>>,
+}
+
+impl<'a> MatcherSet<'a> {
+    /// Compiles every pattern in `patterns`, returning `None` if any of them is
+    /// malformed.
+    fn new(patterns: &[&'a str]) -> Option<MatcherSet<'a>> {
+        let matchers = patterns
+            .iter()
+            .copied()
+            .map(Matcher::new)
+            .collect::<Option<Vec<_>>>()?;
+        Some(MatcherSet { matchers })
+    }
+
+    /// Reports every pattern that matches and fully consumes `input`, each with
+    /// its `(token, slice)` breakdown, ordered so the pattern that matched the
+    /// most tokens comes first. This is the "route an input to the first pattern
+    /// that consumes it" use case, so a pattern that only matches a prefix (and
+    /// leaves a trailing remainder) is not reported.
+    fn matches<'b, 'c>(&'b self, input: &'c str) -> Vec<SetMatch<'a, 'b, 'c>> {
+        let mut result: Vec<SetMatch<'a, 'b, 'c>> = self
+            .matchers
+            .iter()
+            .enumerate()
+            .filter_map(|(pattern, matcher)| {
+                let (end, full, path) = Matcher::run(&matcher.tokens, input, None);
+                (full && end == input.len() && !path.is_empty()).then(|| SetMatch {
+                    pattern,
+                    tokens: path.into_iter().map(|(token, slice, _)| (token, slice)).collect(),
+                })
+            })
+            .collect();
+        result.sort_by_key(|m| Reverse(m.tokens.len()));
+        result
     }
 }
 
@@ -117,7 +660,7 @@ fn main() {
 
 #[cfg(test)]
 mod test {
-    use super::{Matcher, MatcherToken};
+    use super::{Match, Matcher, MatcherBuilder, MatcherSet, MatcherToken, SetMatch};
     #[test]
     fn simple_test() {
         let match_string = "abc(d|e|f).".to_string();
@@ -148,6 +691,182 @@ mod test {
         }
     }
 
+    #[test]
+    fn repetition() {
+        let match_string = "a(b|c)*.".to_string();
+        let mut matcher = Matcher::new(&match_string).unwrap();
+
+        {
+            let candidate = "abcd".to_string();
+            let result = matcher.match_string(&candidate);
+            assert_eq!(
+                result,
+                vec![
+                    (&MatcherToken::RawText("a"), "a"),
+                    (&MatcherToken::OneOfText(vec!["b", "c"]), "b"),
+                    (&MatcherToken::OneOfText(vec!["b", "c"]), "c"),
+                    (&MatcherToken::WildCard, "d"),
+                ]
+            );
+            assert_eq!(matcher.most_tokens_matched, 4);
+        }
+
+        {
+            // Zero repetitions of `(b|c)` still matches, consuming nothing.
+            let candidate = "ax".to_string();
+            let result = matcher.match_string(&candidate);
+            assert_eq!(
+                result,
+                vec![
+                    (&MatcherToken::RawText("a"), "a"),
+                    (&MatcherToken::WildCard, "x"),
+                ]
+            );
+            assert_eq!(matcher.most_tokens_matched, 2);
+        }
+    }
+
+    #[test]
+    fn fuzzy_raw_text() {
+        let match_string = "hello".to_string();
+        let mut matcher = Matcher::new(&match_string).unwrap();
+
+        {
+            // One deletion is within the budget, and the achieved distance is
+            // reported back.
+            let candidate = "helo".to_string();
+            let result = matcher.match_string_fuzzy(&candidate, 1);
+            assert_eq!(result, vec![(&MatcherToken::RawText("hello"), "helo", 1)]);
+        }
+
+        {
+            // Too many edits for the budget, so nothing matches.
+            let candidate = "xyz".to_string();
+            let result = matcher.match_string_fuzzy(&candidate, 1);
+            assert_eq!(result, vec![]);
+        }
+    }
+
+    #[test]
+    fn iterate_matches() {
+        let match_string = "(cat|dog)".to_string();
+        let matcher = Matcher::new(&match_string).unwrap();
+
+        let input = "a cat and a dog".to_string();
+        let found: Vec<_> = matcher.matches(&input).collect();
+
+        assert_eq!(
+            found,
+            vec![
+                Match {
+                    start: 2,
+                    end: 5,
+                    tokens: vec![(&MatcherToken::OneOfText(vec!["cat", "dog"]), "cat")],
+                },
+                Match {
+                    start: 12,
+                    end: 15,
+                    tokens: vec![(&MatcherToken::OneOfText(vec!["cat", "dog"]), "dog")],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn iterate_repetition_matches() {
+        // A trailing `+` must still produce matches through the iterator.
+        let match_string = "ab+".to_string();
+        let matcher = Matcher::new(&match_string).unwrap();
+
+        let input = "zabbbz".to_string();
+        let found: Vec<_> = matcher.matches(&input).collect();
+
+        assert_eq!(
+            found,
+            vec![Match {
+                start: 1,
+                end: 5,
+                tokens: vec![
+                    (&MatcherToken::RawText("a"), "a"),
+                    (&MatcherToken::RawText("b"), "b"),
+                    (&MatcherToken::RawText("b"), "b"),
+                    (&MatcherToken::RawText("b"), "b"),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn crop_and_highlight() {
+        let pattern = "cat".to_string();
+        let matcher = Matcher::new(&pattern).unwrap();
+        let formatter = MatcherBuilder::new()
+            .highlight_prefix("<")
+            .highlight_suffix(">")
+            .crop_marker("[...]");
+
+        {
+            // Window covers the whole input, so nothing is cropped.
+            let input = "cat".to_string();
+            assert_eq!(formatter.format(&matcher, &input, 10), "<cat>");
+        }
+
+        {
+            // The window is cropped around the match on both sides.
+            let input = "a cat here".to_string();
+            assert_eq!(formatter.format(&matcher, &input, 3), "[...]<cat>[...]");
+        }
+    }
+
+    #[test]
+    fn matcher_set() {
+        let patterns = ["abc", "ab."];
+        let set = MatcherSet::new(&patterns).unwrap();
+
+        let input = "abc".to_string();
+        let result = set.matches(&input);
+
+        assert_eq!(
+            result,
+            vec![
+                SetMatch {
+                    pattern: 1,
+                    tokens: vec![
+                        (&MatcherToken::RawText("ab"), "ab"),
+                        (&MatcherToken::WildCard, "c"),
+                    ],
+                },
+                SetMatch {
+                    pattern: 0,
+                    tokens: vec![(&MatcherToken::RawText("abc"), "abc")],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn matcher_set_repetition() {
+        // A pattern ending in a repetition must still be matched by the set.
+        let patterns = ["ab+", "xy"];
+        let set = MatcherSet::new(&patterns).unwrap();
+
+        let input = "abbb".to_string();
+        let result = set.matches(&input);
+
+        assert_eq!(
+            result,
+            vec![SetMatch {
+                pattern: 0,
+                tokens: vec![
+                    (&MatcherToken::RawText("a"), "a"),
+                    (&MatcherToken::RawText("b"), "b"),
+                    (&MatcherToken::RawText("b"), "b"),
+                    (&MatcherToken::RawText("b"), "b"),
+                ],
+            }]
+        );
+    }
+
     #[test]
     fn broken_matcher() {
         let match_string = "abc(d|e|f.".to_string();